@@ -1,10 +1,62 @@
 //! Project changelog
 
 
+/// Release 0.7.1
+///
+/// * Added `recv_with_fd_cloexec` to `RecvWithFd`, which atomically sets `FD_CLOEXEC` on received
+///   descriptors via `MSG_CMSG_CLOEXEC` on Linux, with a portable `fcntl(F_SETFD)` fallback
+///   elsewhere. On Linux a racing `fork`/`exec` can no longer leak them into a child process; the
+///   fallback sets the flag only after the receive, so it is not atomic.
+pub mod r0_7_1 {
+}
+
+/// Release 0.7.0
+///
+/// * Added `UnixSeqpacket`, a `SOCK_SEQPACKET` unix domain socket with `bind`/`listen`/`accept`/
+///   `connect`/`pair` constructors. It implements `SendWithFd`/`RecvWithFd` like `UnixStream` and
+///   `UnixDatagram`, but preserves per-`send` message boundaries like a datagram while keeping
+///   reliable, connection-oriented delivery like a stream.
+pub mod r0_7_0 {
+}
+
+/// Release 0.6.1
+///
+/// * `recv_with_fd` and `recv_with_creds` now check `msghdr.msg_flags` for `MSG_CTRUNC` after
+///   `recvmsg` returns, and report a hard `io::Error` instead of silently returning a partial result
+///   when the kernel had to drop file descriptors because the `fds` buffer was too small.
+pub mod r0_6_1 {
+}
+
+/// Release 0.6.0
+///
+/// * Added `SendWithCreds`/`RecvWithCreds` (Linux-only) to send and receive the sender's process
+///   credentials via `SCM_CREDENTIALS` alongside the bytes and file descriptors.
+/// * Added `set_passcred`, a helper to enable `SO_PASSCRED` on the receiving socket, which the
+///   kernel requires before it will attach `SCM_CREDENTIALS` to received messages.
+pub mod r0_6_0 {
+}
+
+/// Release 0.5.0
+///
+/// * Added `SendWithFdVectored` and `RecvWithFdVectored`, vectored (scatter/gather) counterparts
+///   to `SendWithFd`/`RecvWithFd` that operate over `std::io::IoSlice`/`IoSliceMut` instead of a
+///   single buffer.
+pub mod r0_5_0 {
+}
+
+/// Release 0.4.0
+///
+/// * `send_with_fd` now ORs in `libc::MSG_NOSIGNAL`, so writing to a peer that has closed its end
+///   of the socket is reported as an `EPIPE` `io::Error` rather than raising `SIGPIPE`.
+/// * Added `send_with_fd_flags` and `recv_with_fd_flags` to `SendWithFd`/`RecvWithFd`, allowing
+///   additional `sendmsg`/`recvmsg` flags (such as `MSG_DONTWAIT`) to be passed through.
+pub mod r0_4_0 {
+}
+
 /// Release 0.3.0
 ///
 /// * Removed the `Receivable` trait, because it is difficult to write meaningful code with `<T as
-/// Receivable>` for `T ≠ RawFd`.
+///   Receivable>` for `T ≠ RawFd`.
 pub mod r0_3_0 {
 }
 
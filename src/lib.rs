@@ -5,6 +5,9 @@ use std::os::unix::net;
 use std::os::unix::io::{RawFd, AsRawFd, FromRawFd};
 
 pub mod changelog;
+mod seqpacket;
+
+pub use seqpacket::UnixSeqpacket;
 
 /// Delegate implementation of Receivable or Sendable to a given expression for multiple types at
 /// a time, reducing code duplication significantly.
@@ -64,6 +67,15 @@ pub trait SendWithFd {
     /// Send the bytes and the file descriptors.
     fn send_with_fd<T>(&self, bytes: &[u8], fds: &[T]) -> io::Result<usize>
     where T: Sendable;
+
+    /// Send the bytes and the file descriptors, passing additional `flags` to the underlying
+    /// `sendmsg(2)` call, e.g. `libc::MSG_DONTWAIT`.
+    ///
+    /// `libc::MSG_NOSIGNAL` is always ORed into `flags` regardless of what is passed in, so that
+    /// sending to a peer which has closed its end never raises `SIGPIPE` and is instead reported
+    /// as an `EPIPE` [`io::Error`].
+    fn send_with_fd_flags<T>(&self, bytes: &[u8], fds: &[T], flags: libc::c_int) -> io::Result<usize>
+    where T: Sendable;
 }
 
 /// An extension trait that enables receiving associated file descriptors along with the data.
@@ -73,6 +85,100 @@ pub trait RecvWithFd {
     /// The bytes and the file descriptors are received into the corresponding buffers.
     fn recv_with_fd<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
     where T: Receivable;
+
+    /// Receive the bytes and the file descriptors, passing additional `flags` to the underlying
+    /// `recvmsg(2)` call, e.g. `libc::MSG_DONTWAIT`.
+    fn recv_with_fd_flags<T>(&self, bytes: &mut [u8], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable;
+
+    /// Receive the bytes and the file descriptors, atomically setting `FD_CLOEXEC` on every
+    /// received descriptor so a `fork`/`exec` racing with the receive cannot leak them into a
+    /// child process.
+    ///
+    /// On platforms without atomic support for this (anything but Linux), the flag is instead set
+    /// one descriptor at a time immediately after it is received, which is not quite atomic with
+    /// the receive itself but is the best available fallback.
+    fn recv_with_fd_cloexec<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
+    where T: Receivable;
+}
+
+/// An extension trait that enables sending associated file descriptors along with a list of
+/// buffers in a single scatter/gather `sendmsg(2)` call.
+pub trait SendWithFdVectored {
+    /// Send the buffers and the file descriptors.
+    fn send_vectored_with_fd<T>(&self, bufs: &[io::IoSlice], fds: &[T]) -> io::Result<usize>
+    where T: Sendable;
+
+    /// Like [`send_vectored_with_fd`][SendWithFdVectored::send_vectored_with_fd], but passes
+    /// additional `flags` to the underlying `sendmsg(2)` call. See
+    /// [`send_with_fd_flags`][SendWithFd::send_with_fd_flags] for the `MSG_NOSIGNAL` caveat.
+    fn send_vectored_with_fd_flags<T>(&self, bufs: &[io::IoSlice], fds: &[T], flags: libc::c_int)
+    -> io::Result<usize>
+    where T: Sendable;
+}
+
+/// An extension trait that enables receiving associated file descriptors along with a list of
+/// buffers in a single scatter/gather `recvmsg(2)` call.
+pub trait RecvWithFdVectored {
+    /// Receive into the buffers and the file descriptors.
+    fn recv_vectored_with_fd<T>(&self, bufs: &mut [io::IoSliceMut], fds: &mut [T])
+    -> io::Result<(usize, usize)>
+    where T: Receivable;
+
+    /// Like [`recv_vectored_with_fd`][RecvWithFdVectored::recv_vectored_with_fd], but passes
+    /// additional `flags` to the underlying `recvmsg(2)` call.
+    fn recv_vectored_with_fd_flags<T>(&self, bufs: &mut [io::IoSliceMut], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable;
+}
+
+/// An extension trait that enables sending associated file descriptors together with the
+/// sender's process credentials (`SCM_CREDENTIALS`).
+///
+/// This relies on Linux-specific ancillary data and is therefore only available when building for
+/// Linux.
+#[cfg(target_os = "linux")]
+pub trait SendWithCreds {
+    /// Send the bytes, the file descriptors, and the given credentials.
+    fn send_with_creds<T>(&self, bytes: &[u8], fds: &[T], creds: &libc::ucred) -> io::Result<usize>
+    where T: Sendable;
+}
+
+/// An extension trait that enables receiving the sender's process credentials
+/// (`SCM_CREDENTIALS`) together with any associated file descriptors.
+///
+/// For the kernel to attach the sender's credentials, the receiving socket must have
+/// `SO_PASSCRED` enabled; see [`set_passcred`]. If it is not enabled, `None` is returned in their
+/// place. If it is enabled, the kernel attaches the sender's credentials even when the sender did
+/// not send any explicitly.
+#[cfg(target_os = "linux")]
+pub trait RecvWithCreds {
+    /// Receive the bytes, the file descriptors, and, if present, the sender's credentials.
+    fn recv_with_creds<T>(&self, bytes: &mut [u8], fds: &mut [T])
+    -> io::Result<(usize, usize, Option<libc::ucred>)>
+    where T: Receivable;
+}
+
+/// Enable `SO_PASSCRED` on `socket`, so that the kernel attaches the sender's credentials
+/// (`SCM_CREDENTIALS`) to messages received through [`RecvWithCreds::recv_with_creds`].
+#[cfg(target_os = "linux")]
+pub fn set_passcred<S: AsRawFd>(socket: &S) -> io::Result<()> {
+    unsafe {
+        let enable: libc::c_int = 1;
+        let result = libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PASSCRED,
+            &enable as *const libc::c_int as *const libc::c_void,
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        );
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
 }
 
 delegate! {
@@ -84,7 +190,8 @@ delegate! {
         ::std::os::unix::net::UnixDatagram,
         ::std::os::unix::net::UnixListener,
         ::std::os::unix::net::UnixStream,
-        ::std::process::Stdio
+        ::std::process::Stdio,
+        UnixSeqpacket
     = |fd| unsafe { FromRawFd::from_raw_fd(fd) }
 }
 
@@ -108,7 +215,8 @@ delegate! {
         ::std::os::unix::net::UnixStream,
         ::std::process::ChildStderr,
         ::std::process::ChildStdin,
-        ::std::process::ChildStdout
+        ::std::process::ChildStdout,
+        UnixSeqpacket
     = |this| AsRawFd::as_raw_fd(this)
 }
 
@@ -128,16 +236,26 @@ unsafe fn ptr_offset_from(this: *const u8, origin: *const u8) -> isize {
 ///
 /// The constructed `msghdr` contains the references to the given `iov` and has sufficient
 /// (dynamically allocated) space to store `fd_count` file descriptors delivered as ancillary data.
+/// When `with_creds` is set, additional space for a `SCM_CREDENTIALS` (`libc::ucred`) ancillary
+/// message is reserved as well.
 ///
 /// # Unsafety
 ///
 /// This function provides a "mostly" safe interface, however it is kept unsafe as its only uses
 /// are intended to be in other unsafe code and its implementation itself is also unsafe.
-unsafe fn construct_msghdr_for(iov: &mut libc::iovec, fd_count: usize)
+unsafe fn construct_msghdr_for(iov: &mut [libc::iovec], fd_count: usize, with_creds: bool)
 -> (libc::msghdr, alloc::Layout, usize)
 {
     let fd_len = mem::size_of::<RawFd>() * fd_count;
-    let cmsg_buffer_len = libc::CMSG_SPACE(fd_len as u32) as usize;
+    let mut cmsg_buffer_len = libc::CMSG_SPACE(fd_len as u32) as usize;
+    #[cfg(target_os = "linux")] {
+        if with_creds {
+            cmsg_buffer_len += libc::CMSG_SPACE(mem::size_of::<libc::ucred>() as u32) as usize;
+        }
+    }
+    #[cfg(not(target_os = "linux"))] {
+        let _ = with_creds;
+    }
     let layout = alloc::Layout::from_size_align(cmsg_buffer_len, mem::align_of::<libc::cmsghdr>());
     let (cmsg_buffer, cmsg_layout) = if let Ok(layout) = layout {
         const NULL_MUT_U8: *mut u8 = ptr::null_mut();
@@ -156,8 +274,8 @@ unsafe fn construct_msghdr_for(iov: &mut libc::iovec, fd_count: usize)
     (libc::msghdr {
         msg_name: ptr::null_mut(),
         msg_namelen: 0,
-        msg_iov: iov as *mut _,
-        msg_iovlen: 1,
+        msg_iov: iov.as_mut_ptr(),
+        msg_iovlen: iov.len() as _,
         msg_control: cmsg_buffer,
         msg_controllen: cmsg_buffer_len,
         .. mem::zeroed()
@@ -166,19 +284,230 @@ unsafe fn construct_msghdr_for(iov: &mut libc::iovec, fd_count: usize)
 
 /// A common implementation of `sendmsg` that sends provided bytes with ancillary file descriptors
 /// over either a datagram or stream unix socket.
-fn send_with_fd<T>(socket: RawFd, bs: &[u8], fds: &[T]) -> io::Result<usize>
+///
+/// `libc::MSG_NOSIGNAL` is always ORed into `flags`, so a peer that has closed its end of the
+/// socket is reported as an `EPIPE` [`io::Error`] rather than raising `SIGPIPE`.
+fn send_with_fd<T>(socket: RawFd, bs: &[u8], fds: &[T], flags: libc::c_int) -> io::Result<usize>
+where T: Sendable {
+    let mut iov = [libc::iovec {
+        // NB: this casts *const to *mut, and in doing so we trust the OS to be a good citizen
+        // and not mutate our buffer. This is the API we have to live with.
+        iov_base: bs.as_ptr() as *const _ as *mut _,
+        iov_len: bs.len(),
+    }];
+    unsafe { send_with_fd_iovec(socket, &mut iov, fds, flags) }
+}
+
+/// Like [`send_with_fd`], but sends a whole list of buffers (a "scatter/gather" write) instead of
+/// a single one, mirroring `std::io::Write::write_vectored`.
+fn send_with_fd_vectored<T>(socket: RawFd, bufs: &[io::IoSlice], fds: &[T], flags: libc::c_int)
+-> io::Result<usize>
+where T: Sendable {
+    let mut iov: Vec<libc::iovec> = bufs.iter().map(|buf| libc::iovec {
+        // NB: see the comment in `send_with_fd` about the cast from *const to *mut.
+        iov_base: buf.as_ptr() as *const _ as *mut _,
+        iov_len: buf.len(),
+    }).collect();
+    unsafe { send_with_fd_iovec(socket, &mut iov, fds, flags) }
+}
+
+/// Shared implementation of [`send_with_fd`] and [`send_with_fd_vectored`] parameterized over the
+/// `iov` to send.
+unsafe fn send_with_fd_iovec<T>(socket: RawFd, iov: &mut [libc::iovec], fds: &[T], flags: libc::c_int)
+-> io::Result<usize>
+where T: Sendable {
+    let (mut msghdr, cmsg_layout, fd_len) = construct_msghdr_for(iov, fds.len(), false);
+    let cmsg_buffer = msghdr.msg_control;
+
+    // Fill cmsg with the file descriptors we are sending.
+    let cmsg_header = libc::CMSG_FIRSTHDR(&mut msghdr as *mut _);
+    ptr::write(cmsg_header, libc::cmsghdr {
+        cmsg_level: libc::SOL_SOCKET,
+        cmsg_type: libc::SCM_RIGHTS,
+        cmsg_len: libc::CMSG_LEN(fd_len as u32) as usize,
+    });
+    let cmsg_data = libc::CMSG_DATA(cmsg_header) as *mut RawFd;
+    for (i, fd) in fds.iter().enumerate() {
+        ptr::write_unaligned(cmsg_data.offset(i as isize), <T as Sendable>::as_sendable_fd(fd));
+    }
+    let count = libc::sendmsg(socket, &msghdr as *const _, flags | libc::MSG_NOSIGNAL);
+    if count < 0 {
+        let error = io::Error::last_os_error();
+        alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
+        Err(error)
+    } else {
+        alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
+        Ok(count as usize)
+    }
+}
+
+/// A common implementation of `recvmsg` that receives provided bytes and the ancillary file
+/// descriptors over either a datagram or stream unix socket.
+fn recv_with_fd<T>(socket: RawFd, bs: &mut [u8], fds: &mut [T], flags: libc::c_int)
+-> io::Result<(usize, usize)>
+where T: Receivable {
+    let mut iov = [libc::iovec {
+        iov_base: bs.as_mut_ptr() as *mut _,
+        iov_len: bs.len(),
+    }];
+    let (n, m, _) = unsafe { recv_with_fd_iovec(socket, &mut iov, fds, flags, false, false)? };
+    Ok((n, m))
+}
+
+/// Like [`recv_with_fd`], but receives into a whole list of buffers (a "scatter/gather" read)
+/// instead of a single one, mirroring `std::io::Read::read_vectored`.
+fn recv_with_fd_vectored<T>(socket: RawFd, bufs: &mut [io::IoSliceMut], fds: &mut [T], flags: libc::c_int)
+-> io::Result<(usize, usize)>
+where T: Receivable {
+    let mut iov: Vec<libc::iovec> = bufs.iter_mut().map(|buf| libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut _,
+        iov_len: buf.len(),
+    }).collect();
+    let (n, m, _) = unsafe { recv_with_fd_iovec(socket, &mut iov, fds, flags, false, false)? };
+    Ok((n, m))
+}
+
+/// Like [`recv_with_fd`], but atomically sets `FD_CLOEXEC` on every received descriptor, so that a
+/// `fork`/`exec` racing with the receive can never leak them into a child process.
+///
+/// On Linux this is done by passing `libc::MSG_CMSG_CLOEXEC` to `recvmsg(2)`, which the kernel
+/// honours atomically. Elsewhere, where that flag does not exist, descriptors are instead marked
+/// close-on-exec one at a time via `fcntl(F_SETFD)` as they are walked out of the ancillary data,
+/// which is not atomic with the receive but is the best available fallback.
+fn recv_with_fd_cloexec<T>(socket: RawFd, bs: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
+where T: Receivable {
+    let mut iov = [libc::iovec {
+        iov_base: bs.as_mut_ptr() as *mut _,
+        iov_len: bs.len(),
+    }];
+    #[cfg(target_os = "linux")]
+    let flags = libc::MSG_CMSG_CLOEXEC;
+    #[cfg(not(target_os = "linux"))]
+    let flags = 0;
+    let (n, m, _) = unsafe { recv_with_fd_iovec(socket, &mut iov, fds, flags, true, false)? };
+    Ok((n, m))
+}
+
+/// The credentials optionally produced by [`recv_with_fd_iovec`] when asked to also decode
+/// `SCM_CREDENTIALS`.
+///
+/// `SCM_CREDENTIALS` is a Linux-specific ancillary message, so this is `Option<libc::ucred>` only
+/// on Linux; elsewhere it is a unit type, since [`recv_with_fd_iovec`] is never asked to decode
+/// credentials on those platforms.
+#[cfg(target_os = "linux")]
+type RecvCreds = Option<libc::ucred>;
+#[cfg(not(target_os = "linux"))]
+type RecvCreds = ();
+
+/// Shared implementation of [`recv_with_fd`], [`recv_with_fd_vectored`], [`recv_with_fd_cloexec`]
+/// and [`recv_with_creds`] parameterized over the `iov` to receive into.
+///
+/// `cloexec` requests the portable `fcntl(F_SETFD)` fallback described on
+/// [`recv_with_fd_cloexec`]; on Linux the caller is expected to have already requested
+/// `MSG_CMSG_CLOEXEC` via `flags`, so this is a no-op there. `want_creds` additionally reserves
+/// room for, and decodes, an `SCM_CREDENTIALS` ancillary message; see [`recv_with_creds`].
+unsafe fn recv_with_fd_iovec<T>(
+    socket: RawFd, iov: &mut [libc::iovec], fds: &mut [T], flags: libc::c_int, cloexec: bool,
+    want_creds: bool,
+) -> io::Result<(usize, usize, RecvCreds)>
+where T: Receivable {
+    let (mut msghdr, cmsg_layout, _) = construct_msghdr_for(iov, fds.len(), want_creds);
+    let cmsg_buffer = msghdr.msg_control;
+    let count = libc::recvmsg(socket, &mut msghdr as *mut _, flags);
+    if count < 0 {
+        let error = io::Error::last_os_error();
+        alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
+        return Err(error);
+    }
+    // The kernel may set `MSG_CTRUNC` when the control buffer was too small to hold all of the
+    // ancillary data it wanted to deliver. We still have to walk whatever it *did* deliver below,
+    // because descriptors can arrive (and get installed in our process) even past the point where
+    // truncation kicks in -- e.g. `CMSG_SPACE` rounds up for alignment, which can leave room for
+    // one more descriptor than the buffer was sized for. Those are real, already-open file
+    // descriptors that would otherwise leak silently.
+    let truncated = msghdr.msg_flags & libc::MSG_CTRUNC != 0;
+
+    #[cfg(target_os = "linux")]
+    let mut received_creds: RecvCreds = None;
+    #[cfg(not(target_os = "linux"))]
+    let received_creds: RecvCreds = { let _ = want_creds; () };
+
+    // Walk the ancillary data buffer, collecting every raw descriptor from `SCM_RIGHTS` (and
+    // decoding `SCM_CREDENTIALS`, when `want_creds` is set) before deciding what to do with them.
+    let mut received_fds: Vec<RawFd> = Vec::new();
+    let mut cmsg_header = libc::CMSG_FIRSTHDR(&mut msghdr as *mut _);
+    while !cmsg_header.is_null() {
+        if (*cmsg_header).cmsg_level == libc::SOL_SOCKET
+        && (*cmsg_header).cmsg_type == libc::SCM_RIGHTS {
+            let data_ptr = libc::CMSG_DATA(cmsg_header);
+            let data_offset = ptr_offset_from(data_ptr, cmsg_header as *const _);
+            debug_assert!(data_offset >= 0);
+            let data_byte_count = (*cmsg_header).cmsg_len - data_offset as usize;
+            debug_assert!((*cmsg_header).cmsg_len > data_offset as usize);
+            debug_assert!(data_byte_count % mem::size_of::<RawFd>() == 0);
+            let rawfd_count = (data_byte_count / mem::size_of::<RawFd>()) as isize;
+            for i in 0..rawfd_count {
+                received_fds.push(ptr::read_unaligned((data_ptr as *const RawFd).offset(i)));
+            }
+        }
+        #[cfg(target_os = "linux")] {
+            if want_creds
+            && (*cmsg_header).cmsg_level == libc::SOL_SOCKET
+            && (*cmsg_header).cmsg_type == libc::SCM_CREDENTIALS {
+                let data_ptr = libc::CMSG_DATA(cmsg_header) as *const libc::ucred;
+                received_creds = Some(ptr::read_unaligned(data_ptr));
+            }
+        }
+        cmsg_header = libc::CMSG_NXTHDR(&mut msghdr as *mut _, cmsg_header);
+    }
+    alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
+
+    // The `CMSG_SPACE` rounding mentioned above can also let more than `fds.len()` descriptors
+    // through without the kernel setting `MSG_CTRUNC`; that is treated as truncation as well.
+    if truncated || received_fds.len() > fds.len() {
+        // Close every descriptor the kernel handed us: we are about to report this as a hard
+        // error, so none of these fds will be reachable through `fds` and they would otherwise
+        // leak.
+        for fd in received_fds {
+            libc::close(fd);
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "ancillary data was truncated: the `fds` buffer was too small to hold all of the \
+             file descriptors the sender attached",
+        ));
+    }
+
+    // Past the check above, `fds` has room for every descriptor the kernel handed us.
+    debug_assert!(received_fds.len() <= fds.len());
+    let descriptor_count = received_fds.len();
+    for (dst, fd) in fds.iter_mut().zip(received_fds) {
+        #[cfg(not(target_os = "linux"))] {
+            if cloexec {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+        #[cfg(target_os = "linux")] { let _ = cloexec; }
+        *dst = <T as Receivable>::from_received_fd(fd);
+    }
+    Ok((count as usize, descriptor_count, received_creds))
+}
+
+/// A common implementation of `sendmsg` that sends provided bytes with ancillary file descriptors
+/// and the given process credentials (`SCM_CREDENTIALS`) over either a datagram or stream unix
+/// socket.
+#[cfg(target_os = "linux")]
+fn send_with_creds<T>(socket: RawFd, bs: &[u8], fds: &[T], creds: &libc::ucred) -> io::Result<usize>
 where T: Sendable {
     unsafe {
-        let mut iov = libc::iovec {
-            // NB: this casts *const to *mut, and in doing so we trust the OS to be a good citizen
-            // and not mutate our buffer. This is the API we have to live with.
+        let mut iov = [libc::iovec {
             iov_base: bs.as_ptr() as *const _ as *mut _,
             iov_len: bs.len(),
-        };
-        let (mut msghdr, cmsg_layout, fd_len) = construct_msghdr_for(&mut iov, fds.len());
+        }];
+        let (mut msghdr, cmsg_layout, fd_len) = construct_msghdr_for(&mut iov, fds.len(), true);
         let cmsg_buffer = msghdr.msg_control;
 
-        // Fill cmsg with the file descriptors we are sending.
+        // Fill in the `SCM_RIGHTS` cmsg with the file descriptors we are sending.
         let cmsg_header = libc::CMSG_FIRSTHDR(&mut msghdr as *mut _);
         ptr::write(cmsg_header, libc::cmsghdr {
             cmsg_level: libc::SOL_SOCKET,
@@ -189,75 +518,43 @@ where T: Sendable {
         for (i, fd) in fds.iter().enumerate() {
             ptr::write_unaligned(cmsg_data.offset(i as isize), <T as Sendable>::as_sendable_fd(fd));
         }
-        let count = libc::sendmsg(socket, &msghdr as *const _, 0);
-        if count < 0 {
-            let error = io::Error::last_os_error();
-            alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
-            Err(error)
+
+        // Fill in the `SCM_CREDENTIALS` cmsg with the credentials we are sending.
+        let creds_len = mem::size_of::<libc::ucred>();
+        let creds_header = libc::CMSG_NXTHDR(&mut msghdr as *mut _, cmsg_header);
+        ptr::write(creds_header, libc::cmsghdr {
+            cmsg_level: libc::SOL_SOCKET,
+            cmsg_type: libc::SCM_CREDENTIALS,
+            cmsg_len: libc::CMSG_LEN(creds_len as u32) as usize,
+        });
+        ptr::write_unaligned(libc::CMSG_DATA(creds_header) as *mut libc::ucred, *creds);
+
+        let count = libc::sendmsg(socket, &msghdr as *const _, libc::MSG_NOSIGNAL);
+        let result = if count < 0 {
+            Err(io::Error::last_os_error())
         } else {
-            alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
             Ok(count as usize)
-        }
+        };
+        alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
+        result
     }
 }
 
-/// A common implementation of `recvmsg` that receives provided bytes and the ancillary file
-/// descriptors over either a datagram or stream unix socket.
-fn recv_with_fd<T>(socket: RawFd, bs: &mut [u8], mut fds: &mut [T]) -> io::Result<(usize, usize)>
+/// A common implementation of `recvmsg` that receives provided bytes, the ancillary file
+/// descriptors, and the sender's credentials (`SCM_CREDENTIALS`), over either a datagram or stream
+/// unix socket.
+///
+/// This is a thin wrapper around [`recv_with_fd_iovec`] with `want_creds` set, so the cmsg-walking
+/// logic lives in exactly one place shared with [`recv_with_fd`].
+#[cfg(target_os = "linux")]
+fn recv_with_creds<T>(socket: RawFd, bs: &mut [u8], fds: &mut [T])
+-> io::Result<(usize, usize, Option<libc::ucred>)>
 where T: Receivable {
-    unsafe {
-        let mut iov = libc::iovec {
-            iov_base: bs.as_mut_ptr() as *mut _,
-            iov_len: bs.len(),
-        };
-        let (mut msghdr, cmsg_layout, _) = construct_msghdr_for(&mut iov, fds.len());
-        let cmsg_buffer = msghdr.msg_control;
-        let count = libc::recvmsg(socket, &mut msghdr as *mut _, 0);
-        if count < 0 {
-            let error = io::Error::last_os_error();
-            alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
-            return Err(error);
-        }
-
-        // Walk the ancillary data buffer and copy the raw descriptors from it into the output
-        // buffer.
-        let mut descriptor_count = 0;
-        let mut cmsg_header = libc::CMSG_FIRSTHDR(&mut msghdr as *mut _);
-        while !cmsg_header.is_null() {
-            if (*cmsg_header).cmsg_level == libc::SOL_SOCKET
-            && (*cmsg_header).cmsg_type == libc::SCM_RIGHTS {
-                let data_ptr = libc::CMSG_DATA(cmsg_header);
-                let data_offset = ptr_offset_from(data_ptr, cmsg_header as *const _);
-                debug_assert!(data_offset >= 0);
-                let data_byte_count = (*cmsg_header).cmsg_len - data_offset as usize;
-                debug_assert!((*cmsg_header).cmsg_len > data_offset as usize);
-                debug_assert!(data_byte_count % mem::size_of::<RawFd>() == 0);
-                let rawfd_count = (data_byte_count / mem::size_of::<RawFd>()) as isize;
-                for i in 0..rawfd_count {
-                    if let Some((dst, rest)) = {fds}.split_first_mut() {
-                        *dst = <T as Receivable>::from_received_fd(
-                            ptr::read_unaligned((data_ptr as *const RawFd).offset(i))
-                        );
-                        descriptor_count += 1;
-                        fds = rest;
-                    } else {
-                        // This branch is unreachable. We allocate the ancillary data buffer just
-                        // large enough to fit exactly the number of `RawFd`s that are in the `fds`
-                        // buffer. It is not possible for the OS to return more of them.
-                        //
-                        // If this branch ended up being reachable for some reason, it would be
-                        // necessary for this branch to close the file descriptors to avoid leaking
-                        // resources.
-                        unreachable!();
-                    }
-                }
-            }
-            cmsg_header = libc::CMSG_NXTHDR(&mut msghdr as *mut _, cmsg_header);
-        }
-
-        alloc::dealloc(cmsg_buffer as *mut _, cmsg_layout);
-        Ok((count as usize, descriptor_count))
-    }
+    let mut iov = [libc::iovec {
+        iov_base: bs.as_mut_ptr() as *mut _,
+        iov_len: bs.len(),
+    }];
+    unsafe { recv_with_fd_iovec(socket, &mut iov, fds, 0, false, true) }
 }
 
 impl SendWithFd for net::UnixStream {
@@ -267,7 +564,12 @@ impl SendWithFd for net::UnixStream {
     /// may arrive entirely independently.
     fn send_with_fd<T>(&self, bytes: &[u8], fds: &[T]) -> io::Result<usize>
     where T: Sendable {
-        send_with_fd(self.as_raw_fd(), bytes, fds)
+        self.send_with_fd_flags(bytes, fds, 0)
+    }
+
+    fn send_with_fd_flags<T>(&self, bytes: &[u8], fds: &[T], flags: libc::c_int) -> io::Result<usize>
+    where T: Sendable {
+        send_with_fd(self.as_raw_fd(), bytes, fds, flags)
     }
 }
 
@@ -279,7 +581,12 @@ impl SendWithFd for net::UnixDatagram {
     /// small.
     fn send_with_fd<T>(&self, bytes: &[u8], fds: &[T]) -> io::Result<usize>
     where T: Sendable {
-        send_with_fd(self.as_raw_fd(), bytes, fds)
+        self.send_with_fd_flags(bytes, fds, 0)
+    }
+
+    fn send_with_fd_flags<T>(&self, bytes: &[u8], fds: &[T], flags: libc::c_int) -> io::Result<usize>
+    where T: Sendable {
+        send_with_fd(self.as_raw_fd(), bytes, fds, flags)
     }
 }
 
@@ -292,7 +599,18 @@ impl RecvWithFd for net::UnixStream {
     /// that were sent with a single `send_with_fd` call by somebody else.
     fn recv_with_fd<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
     where T: Receivable {
-        recv_with_fd(self.as_raw_fd(), bytes, fds)
+        self.recv_with_fd_flags(bytes, fds, 0)
+    }
+
+    fn recv_with_fd_flags<T>(&self, bytes: &mut [u8], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd(self.as_raw_fd(), bytes, fds, flags)
+    }
+
+    fn recv_with_fd_cloexec<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd_cloexec(self.as_raw_fd(), bytes, fds)
     }
 }
 
@@ -305,20 +623,167 @@ impl RecvWithFd for net::UnixDatagram {
     /// undelivered data will be discarded.
     ///
     /// For receiving the file descriptors, the internal buffer is sized according to the size of
-    /// the `fds` buffer. If the sender sends `fds.len()` descriptors, but prefaces the descriptors
-    /// with some other ancilliary data, then some file descriptors may be truncated as well.
+    /// the `fds` buffer. If the sender sends more descriptors than `fds.len()` -- for instance
+    /// because it also prefaces them with some other ancillary data -- the excess descriptors are
+    /// not silently dropped: this returns a hard `io::Error` instead.
     fn recv_with_fd<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
     where T: Receivable {
-        recv_with_fd(self.as_raw_fd(), bytes, fds)
+        self.recv_with_fd_flags(bytes, fds, 0)
+    }
+
+    fn recv_with_fd_flags<T>(&self, bytes: &mut [u8], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd(self.as_raw_fd(), bytes, fds, flags)
+    }
+
+    fn recv_with_fd_cloexec<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd_cloexec(self.as_raw_fd(), bytes, fds)
+    }
+}
+
+impl SendWithFdVectored for net::UnixStream {
+    /// Send the buffers and the file descriptors as a stream.
+    ///
+    /// Neither is guaranteed to be received by the other end in a single chunk and
+    /// may arrive entirely independently.
+    fn send_vectored_with_fd<T>(&self, bufs: &[io::IoSlice], fds: &[T]) -> io::Result<usize>
+    where T: Sendable {
+        self.send_vectored_with_fd_flags(bufs, fds, 0)
+    }
+
+    fn send_vectored_with_fd_flags<T>(&self, bufs: &[io::IoSlice], fds: &[T], flags: libc::c_int)
+    -> io::Result<usize>
+    where T: Sendable {
+        send_with_fd_vectored(self.as_raw_fd(), bufs, fds, flags)
+    }
+}
+
+impl SendWithFdVectored for net::UnixDatagram {
+    /// Send the buffers and the file descriptors as a single packet.
+    ///
+    /// It is guaranteed that the bytes and the associated file descriptors will arrive at the same
+    /// time, however the receiver end may not receive the full message if its buffers are too
+    /// small.
+    fn send_vectored_with_fd<T>(&self, bufs: &[io::IoSlice], fds: &[T]) -> io::Result<usize>
+    where T: Sendable {
+        self.send_vectored_with_fd_flags(bufs, fds, 0)
+    }
+
+    fn send_vectored_with_fd_flags<T>(&self, bufs: &[io::IoSlice], fds: &[T], flags: libc::c_int)
+    -> io::Result<usize>
+    where T: Sendable {
+        send_with_fd_vectored(self.as_raw_fd(), bufs, fds, flags)
+    }
+}
+
+impl RecvWithFdVectored for net::UnixStream {
+    /// Receive into the buffers and the file descriptors from the stream.
+    ///
+    /// It is not guaranteed that the received information will form a single coherent packet of
+    /// data. In other words, it is not required that this receives the bytes and file descriptors
+    /// that were sent with a single `send_with_fd` call by somebody else.
+    fn recv_vectored_with_fd<T>(&self, bufs: &mut [io::IoSliceMut], fds: &mut [T])
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        self.recv_vectored_with_fd_flags(bufs, fds, 0)
+    }
+
+    fn recv_vectored_with_fd_flags<T>(&self, bufs: &mut [io::IoSliceMut], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd_vectored(self.as_raw_fd(), bufs, fds, flags)
+    }
+}
+
+impl RecvWithFdVectored for net::UnixDatagram {
+    /// Receive into the buffers and the file descriptors as a single packet.
+    ///
+    /// It is guaranteed that the received information will form a single coherent packet, and data
+    /// received will match a corresponding `send_with_fd` call. Note, however, that in case the
+    /// receiving buffer(s) are to small, the message may get silently truncated and the
+    /// undelivered data will be discarded.
+    ///
+    /// For receiving the file descriptors, the internal buffer is sized according to the size of
+    /// the `fds` buffer. If the sender sends more descriptors than `fds.len()` -- for instance
+    /// because it also prefaces them with some other ancillary data -- the excess descriptors are
+    /// not silently dropped: this returns a hard `io::Error` instead.
+    fn recv_vectored_with_fd<T>(&self, bufs: &mut [io::IoSliceMut], fds: &mut [T])
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        self.recv_vectored_with_fd_flags(bufs, fds, 0)
+    }
+
+    fn recv_vectored_with_fd_flags<T>(&self, bufs: &mut [io::IoSliceMut], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd_vectored(self.as_raw_fd(), bufs, fds, flags)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SendWithCreds for net::UnixStream {
+    fn send_with_creds<T>(&self, bytes: &[u8], fds: &[T], creds: &libc::ucred) -> io::Result<usize>
+    where T: Sendable {
+        send_with_creds(self.as_raw_fd(), bytes, fds, creds)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl SendWithCreds for net::UnixDatagram {
+    fn send_with_creds<T>(&self, bytes: &[u8], fds: &[T], creds: &libc::ucred) -> io::Result<usize>
+    where T: Sendable {
+        send_with_creds(self.as_raw_fd(), bytes, fds, creds)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl RecvWithCreds for net::UnixStream {
+    fn recv_with_creds<T>(&self, bytes: &mut [u8], fds: &mut [T])
+    -> io::Result<(usize, usize, Option<libc::ucred>)>
+    where T: Receivable {
+        recv_with_creds(self.as_raw_fd(), bytes, fds)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl RecvWithCreds for net::UnixDatagram {
+    fn recv_with_creds<T>(&self, bytes: &mut [u8], fds: &mut [T])
+    -> io::Result<(usize, usize, Option<libc::ucred>)>
+    where T: Receivable {
+        recv_with_creds(self.as_raw_fd(), bytes, fds)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::os::unix::net;
-    use super::{SendWithFd, RecvWithFd};
+    use std::io::{IoSlice, IoSliceMut};
+    use super::{SendWithFd, RecvWithFd, SendWithFdVectored, RecvWithFdVectored};
     use std::os::unix::io::{AsRawFd, FromRawFd};
 
+    #[test]
+    fn datagram_vectored_works() {
+        let (l, r) = net::UnixDatagram::pair().expect("create UnixDatagram pair");
+        let header = b"header:";
+        let payload = b"hello world!";
+        let sent_fds = [l.as_raw_fd(), r.as_raw_fd()];
+        let sent_bufs = [IoSlice::new(&header[..]), IoSlice::new(&payload[..])];
+        assert_eq!(l.send_vectored_with_fd(&sent_bufs, &sent_fds[..])
+                    .expect("send should be successful"),
+                   header.len() + payload.len());
+        let mut recv_header = [0; 7];
+        let mut recv_payload = [0; 128];
+        let mut recv_fds = [0, 0, 0, 0, 0, 0, 0];
+        let mut recv_bufs = [IoSliceMut::new(&mut recv_header), IoSliceMut::new(&mut recv_payload)];
+        assert_eq!(r.recv_vectored_with_fd(&mut recv_bufs, &mut recv_fds)
+                    .expect("recv should be successful"),
+                   (header.len() + payload.len(), sent_fds.len()));
+        assert_eq!(&recv_header[..], &header[..]);
+        assert_eq!(recv_payload[..payload.len()], payload[..]);
+    }
+
     #[test]
     fn stream_works() {
         let (l, r) = net::UnixStream::pair().expect("create UnixStream pair");
@@ -349,6 +814,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn send_to_closed_peer_returns_broken_pipe() {
+        let (l, r) = net::UnixStream::pair().expect("create UnixStream pair");
+        drop(r);
+        let sent_bytes = b"hello world!";
+        let sent_fds: [i32; 0] = [];
+        let error = l.send_with_fd(&sent_bytes[..], &sent_fds[..])
+            .expect_err("send to a peer with no read end left should fail");
+        assert_eq!(error.kind(), std::io::ErrorKind::BrokenPipe);
+    }
+
+    #[test]
+    fn seqpacket_works() {
+        use super::UnixSeqpacket;
+
+        let (l, r) = UnixSeqpacket::pair().expect("create UnixSeqpacket pair");
+        let sent_bytes = b"hello world!";
+        let sent_fds = [l.as_raw_fd(), r.as_raw_fd()];
+        assert_eq!(l.send_with_fd(&sent_bytes[..], &sent_fds[..])
+                    .expect("send should be successful"),
+                   sent_bytes.len());
+        let mut recv_bytes = [0; 128];
+        let mut recv_fds = [0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(r.recv_with_fd(&mut recv_bytes, &mut recv_fds)
+                    .expect("recv should be successful"),
+                   (sent_bytes.len(), sent_fds.len()));
+        assert_eq!(recv_bytes[..sent_bytes.len()], sent_bytes[..]);
+    }
+
+    #[test]
+    fn seqpacket_preserves_message_boundaries() {
+        use super::UnixSeqpacket;
+
+        let (l, r) = UnixSeqpacket::pair().expect("create UnixSeqpacket pair");
+        let first = b"first message";
+        let second = b"second, longer message";
+        let sent_fds: [i32; 0] = [];
+        l.send_with_fd(&first[..], &sent_fds[..]).expect("first send should be successful");
+        l.send_with_fd(&second[..], &sent_fds[..]).expect("second send should be successful");
+
+        let mut recv_bytes = [0; 128];
+        let mut recv_fds: [i32; 0] = [];
+        assert_eq!(r.recv_with_fd(&mut recv_bytes, &mut recv_fds)
+                    .expect("first recv should be successful"),
+                   (first.len(), 0));
+        assert_eq!(recv_bytes[..first.len()], first[..]);
+
+        let mut recv_bytes = [0; 128];
+        assert_eq!(r.recv_with_fd(&mut recv_bytes, &mut recv_fds)
+                    .expect("second recv should be successful"),
+                   (second.len(), 0));
+        assert_eq!(recv_bytes[..second.len()], second[..]);
+    }
+
+    #[test]
+    fn seqpacket_bind_listen_connect_accept() {
+        use super::UnixSeqpacket;
+
+        let path = std::env::temp_dir().join(
+            format!("sendfd-test-seqpacket-{}.sock", std::process::id())
+        );
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixSeqpacket::bind(&path).expect("bind should be successful");
+        listener.listen(1).expect("listen should be successful");
+
+        let client = UnixSeqpacket::connect(&path).expect("connect should be successful");
+        let server = listener.accept().expect("accept should be successful");
+        std::fs::remove_file(&path).expect("remove socket file");
+
+        let sent_bytes = b"hello from the client";
+        let sent_fds: [i32; 0] = [];
+        client.send_with_fd(&sent_bytes[..], &sent_fds[..])
+            .expect("send should be successful");
+        let mut recv_bytes = [0; 128];
+        let mut recv_fds: [i32; 0] = [];
+        assert_eq!(server.recv_with_fd(&mut recv_bytes, &mut recv_fds)
+                    .expect("recv should be successful"),
+                   (sent_bytes.len(), 0));
+        assert_eq!(recv_bytes[..sent_bytes.len()], sent_bytes[..]);
+    }
+
     #[test]
     fn datagram_works() {
         let (l, r) = net::UnixDatagram::pair().expect("create UnixDatagram pair");
@@ -442,4 +988,83 @@ mod tests {
             panic!("expected an error when sending a junk file descriptor");
         }
     }
+
+    #[test]
+    fn recv_with_fd_cloexec_sets_cloexec() {
+        let (l, r) = net::UnixDatagram::pair().expect("create UnixDatagram pair");
+        let sent_bytes = b"hello world!";
+        let sent_fds = [l.as_raw_fd()];
+        l.send_with_fd(&sent_bytes[..], &sent_fds[..]).expect("send should be successful");
+        let mut recv_bytes = [0; 128];
+        let mut recv_fds = [0];
+        r.recv_with_fd_cloexec(&mut recv_bytes, &mut recv_fds).expect("recv should be successful");
+        let flags = unsafe { libc::fcntl(recv_fds[0], libc::F_GETFD) };
+        assert_eq!(flags & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+        unsafe { libc::close(recv_fds[0]); }
+    }
+
+    #[test]
+    fn recv_with_fd_reports_truncation_without_leaking_fds() {
+        // Checking `/proc/self/fd` is only meaningful in a process that isn't also running other
+        // tests concurrently, so fork (as `datagram_works_across_processes` above does) and do
+        // the check in an otherwise-idle child.
+        unsafe {
+            match libc::fork() {
+                -1 => panic!("fork failed!"),
+                0 => {
+                    let (l, r) = net::UnixDatagram::pair().expect("create UnixDatagram pair");
+                    let sent_bytes = b"hello world!";
+                    // Send more descriptors than the receiver's `fds` buffer below has room for.
+                    let sent_fds = [l.as_raw_fd(), l.as_raw_fd(), l.as_raw_fd()];
+                    l.send_with_fd(&sent_bytes[..], &sent_fds[..]).expect("send should be successful");
+
+                    let open_fds_before = open_fd_count();
+                    let mut recv_bytes = [0; 128];
+                    let mut recv_fds = [0];
+                    let result = r.recv_with_fd(&mut recv_bytes, &mut recv_fds);
+                    let ok = matches!(&result, Err(e) if e.kind() == std::io::ErrorKind::Other)
+                        && open_fd_count() == open_fds_before;
+                    ::std::process::exit(if ok { 0 } else { 1 });
+                }
+                pid => {
+                    let mut status = 0;
+                    libc::waitpid(pid, &mut status, 0);
+                    assert_eq!(status, 0, "truncated recv_with_fd leaked a received descriptor");
+                }
+            }
+        }
+    }
+
+    /// The number of file descriptors currently open in this process, used to detect leaks.
+    fn open_fd_count() -> usize {
+        std::fs::read_dir("/proc/self/fd").expect("read /proc/self/fd").count()
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn datagram_with_creds_works() {
+        use super::{SendWithCreds, RecvWithCreds};
+
+        let (l, r) = net::UnixDatagram::pair().expect("create UnixDatagram pair");
+        super::set_passcred(&r).expect("enable SO_PASSCRED");
+        let sent_bytes = b"hello world!";
+        let sent_fds: [i32; 0] = [];
+        // The kernel rejects credentials that do not match the sending process unless it holds
+        // the matching capabilities, so claim our own.
+        let creds = unsafe {
+            libc::ucred { pid: libc::getpid(), uid: libc::getuid(), gid: libc::getgid() }
+        };
+        assert_eq!(l.send_with_creds(&sent_bytes[..], &sent_fds[..], &creds)
+                    .expect("send should be successful"),
+                   sent_bytes.len());
+        let mut recv_bytes = [0; 128];
+        let mut recv_fds: [i32; 0] = [];
+        let (n, fd_count, recv_creds) = r.recv_with_creds(&mut recv_bytes, &mut recv_fds)
+            .expect("recv should be successful");
+        assert_eq!((n, fd_count), (sent_bytes.len(), 0));
+        let recv_creds = recv_creds.expect("kernel should have attached SCM_CREDENTIALS");
+        assert!(recv_creds.pid > 0);
+        assert_eq!(recv_creds.uid, unsafe { libc::getuid() });
+        assert_eq!(recv_creds.gid, unsafe { libc::getgid() });
+    }
 }
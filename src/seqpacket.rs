@@ -0,0 +1,209 @@
+//! `SOCK_SEQPACKET` unix sockets.
+//!
+//! `std` only exposes `UnixStream` and `UnixDatagram`, neither of which quite fits every use
+//! case: a stream has no message boundaries, while a datagram has no notion of a connection. A
+//! seqpacket socket is connection-oriented like a stream, but (like a datagram) preserves message
+//! boundaries, so each `recv_with_fd` returns exactly the bytes and descriptors from a single
+//! `send_with_fd` call.
+
+use std::ffi::CString;
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::Path;
+
+use super::{send_with_fd, recv_with_fd, recv_with_fd_cloexec, RecvWithFd, Receivable, SendWithFd, Sendable};
+
+/// A `SOCK_SEQPACKET` unix domain socket.
+///
+/// Like [`std::os::unix::net::UnixStream`], this represents a connection-oriented socket, but
+/// unlike a stream, each `send_with_fd` call is delivered to the peer as a discrete message: a
+/// single `recv_with_fd` call will never return bytes or file descriptors belonging to more than
+/// one `send_with_fd` call. If the receiving byte buffer is too small for a message, the rest of
+/// that message is discarded.
+pub struct UnixSeqpacket(RawFd);
+
+impl UnixSeqpacket {
+    fn new() -> io::Result<Self> {
+        #[cfg(target_os = "linux")]
+        let socket_type = libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC;
+        #[cfg(not(target_os = "linux"))]
+        let socket_type = libc::SOCK_SEQPACKET;
+        let fd = unsafe { libc::socket(libc::AF_UNIX, socket_type, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        #[cfg(not(target_os = "linux"))]
+        unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC); }
+        Ok(UnixSeqpacket(fd))
+    }
+
+    /// Connect to the seqpacket socket bound to the specified path.
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let socket = Self::new()?;
+        let (addr, addr_len) = sockaddr_un(path.as_ref())?;
+        let result = unsafe {
+            libc::connect(socket.as_raw_fd(), &addr as *const _ as *const libc::sockaddr, addr_len)
+        };
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(socket)
+        }
+    }
+
+    /// Create a seqpacket socket bound to the specified path, ready to be [`listen`]ed on.
+    ///
+    /// [`listen`]: UnixSeqpacket::listen
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let socket = Self::new()?;
+        let (addr, addr_len) = sockaddr_un(path.as_ref())?;
+        let result = unsafe {
+            libc::bind(socket.as_raw_fd(), &addr as *const _ as *const libc::sockaddr, addr_len)
+        };
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(socket)
+        }
+    }
+
+    /// Mark this socket as ready to accept incoming connections via [`accept`].
+    ///
+    /// [`accept`]: UnixSeqpacket::accept
+    pub fn listen(&self, backlog: i32) -> io::Result<()> {
+        let result = unsafe { libc::listen(self.as_raw_fd(), backlog) };
+        if result < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Accept a single incoming connection on a socket previously set up with [`bind`] and
+    /// [`listen`].
+    ///
+    /// [`bind`]: UnixSeqpacket::bind
+    /// [`listen`]: UnixSeqpacket::listen
+    pub fn accept(&self) -> io::Result<Self> {
+        #[cfg(target_os = "linux")]
+        let fd = unsafe {
+            libc::accept4(self.as_raw_fd(), ptr_null_mut(), ptr_null_mut(), libc::SOCK_CLOEXEC)
+        };
+        #[cfg(not(target_os = "linux"))]
+        let fd = unsafe { libc::accept(self.as_raw_fd(), ptr_null_mut(), ptr_null_mut()) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        #[cfg(not(target_os = "linux"))]
+        unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC); }
+        Ok(UnixSeqpacket(fd))
+    }
+
+    /// Create a connected pair of seqpacket sockets, analogous to
+    /// [`UnixStream::pair`][std::os::unix::net::UnixStream::pair].
+    pub fn pair() -> io::Result<(Self, Self)> {
+        #[cfg(target_os = "linux")]
+        let socket_type = libc::SOCK_SEQPACKET | libc::SOCK_CLOEXEC;
+        #[cfg(not(target_os = "linux"))]
+        let socket_type = libc::SOCK_SEQPACKET;
+        let mut fds = [0; 2];
+        let result = unsafe {
+            libc::socketpair(libc::AF_UNIX, socket_type, 0, fds.as_mut_ptr())
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        #[cfg(not(target_os = "linux"))] {
+            unsafe {
+                libc::fcntl(fds[0], libc::F_SETFD, libc::FD_CLOEXEC);
+                libc::fcntl(fds[1], libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+        }
+        Ok((UnixSeqpacket(fds[0]), UnixSeqpacket(fds[1])))
+    }
+}
+
+impl Drop for UnixSeqpacket {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.0); }
+    }
+}
+
+impl AsRawFd for UnixSeqpacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.0
+    }
+}
+
+impl FromRawFd for UnixSeqpacket {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        UnixSeqpacket(fd)
+    }
+}
+
+impl SendWithFd for UnixSeqpacket {
+    /// Send the bytes and the file descriptors as a single message.
+    ///
+    /// Unlike `UnixStream`, the message boundary is preserved: the peer's `recv_with_fd` call
+    /// will receive exactly the bytes and file descriptors sent here, and no more.
+    fn send_with_fd<T>(&self, bytes: &[u8], fds: &[T]) -> io::Result<usize>
+    where T: Sendable {
+        self.send_with_fd_flags(bytes, fds, 0)
+    }
+
+    fn send_with_fd_flags<T>(&self, bytes: &[u8], fds: &[T], flags: libc::c_int) -> io::Result<usize>
+    where T: Sendable {
+        send_with_fd(self.as_raw_fd(), bytes, fds, flags)
+    }
+}
+
+impl RecvWithFd for UnixSeqpacket {
+    /// Receive the bytes and the file descriptors from a single message.
+    ///
+    /// It is guaranteed that the received information will form a single coherent message, and
+    /// will match a corresponding `send_with_fd` call. Note, however, that in case the receiving
+    /// byte buffer is too small, the message may get silently truncated and the undelivered data
+    /// discarded. If `fds` is too small to hold every descriptor the sender attached, that is not
+    /// silent: this returns a hard `io::Error` instead.
+    fn recv_with_fd<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
+    where T: Receivable {
+        self.recv_with_fd_flags(bytes, fds, 0)
+    }
+
+    fn recv_with_fd_flags<T>(&self, bytes: &mut [u8], fds: &mut [T], flags: libc::c_int)
+    -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd(self.as_raw_fd(), bytes, fds, flags)
+    }
+
+    fn recv_with_fd_cloexec<T>(&self, bytes: &mut [u8], fds: &mut [T]) -> io::Result<(usize, usize)>
+    where T: Receivable {
+        recv_with_fd_cloexec(self.as_raw_fd(), bytes, fds)
+    }
+}
+
+fn ptr_null_mut<T>() -> *mut T {
+    std::ptr::null_mut()
+}
+
+/// Construct a `libc::sockaddr_un` for the given path, the way `bind`/`connect` expect it.
+fn sockaddr_un(path: &Path) -> io::Result<(libc::sockaddr_un, libc::socklen_t)> {
+    let mut addr: libc::sockaddr_un = unsafe { mem::zeroed() };
+    addr.sun_family = libc::AF_UNIX as libc::sa_family_t;
+
+    let bytes = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "path contained a NUL byte"))?;
+    let bytes = bytes.as_bytes_with_nul();
+    if bytes.len() > addr.sun_path.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "path is too long for sun_path"));
+    }
+    for (dst, &src) in addr.sun_path.iter_mut().zip(bytes) {
+        *dst = src as libc::c_char;
+    }
+
+    let base_len = mem::size_of::<libc::sa_family_t>();
+    let addr_len = base_len + bytes.len();
+    Ok((addr, addr_len as libc::socklen_t))
+}